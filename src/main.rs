@@ -1,4 +1,4 @@
-use gix::{Id, Repository, bstr::BString, hash::Prefix};
+use gix::{Id, Repository, bstr::BString, hash::Prefix, remote::Direction};
 use owo_colors::OwoColorize;
 use std::{
 	fmt::{Display, Write},
@@ -16,16 +16,127 @@ fn rel(rev: Id) -> Prefix {
 	rev.shorten().unwrap()
 }
 
+fn resolve<'a>(repo: &'a Repository, rev: &str) -> Id<'a> {
+	let rev = rev.trim();
+	repo.rev_parse_single(rev).unwrap()
+}
+
 fn hash(repo: &Repository, hash: &str) -> Prefix {
-	let hash = hash.trim();
-	let hash = repo.rev_parse_single(hash).unwrap();
-	rel(hash)
+	rel(resolve(repo, hash))
+}
+
+// a prompt can't afford to walk the full history on every render, so cap
+// how many commits back we'll look for a tag
+const DESCRIBE_DEPTH: usize = 1000;
+
+// nearest reachable tag, and how many commits away it is. breadth-first
+// so the first tag found really is the nearest one (true commit
+// distance), rather than just the first one hit in rev-walk order, which
+// can differ once there are merges in the way.
+fn nearest_tag(repo: &Repository, start: Id<'_>) -> Option<(BString, usize)> {
+	let refs = repo.references().ok()?;
+	let tags: std::collections::HashMap<_, _> = refs
+		.tags()
+		.ok()?
+		.flatten()
+		.filter_map(|tag| {
+			let name = tag.name().shorten().to_owned();
+			let id = tag.clone().into_fully_peeled_id().ok()?.detach();
+			Some((id, name))
+		})
+		.collect();
+
+	if tags.is_empty() {
+		return None;
+	}
+
+	let mut seen = std::collections::HashSet::new();
+	let mut frontier = std::collections::VecDeque::new();
+	seen.insert(start.detach());
+	frontier.push_back((start.detach(), 0));
+
+	while let Some((id, distance)) = frontier.pop_front() {
+		if let Some(name) = tags.get(&id) {
+			return Some((name.clone(), distance));
+		}
+		if distance >= DESCRIBE_DEPTH {
+			continue;
+		}
+
+		let Ok(commit) = repo.find_commit(id) else {
+			continue;
+		};
+		for parent in commit.parent_ids() {
+			if seen.insert(parent.detach()) {
+				frontier.push_back((parent.detach(), distance + 1));
+			}
+		}
+	}
+
+	None
+}
+
+// local branch (if any) that this commit is an ancestor of. uses
+// merge-base rather than walking each branch tip's full ancestry, so
+// membership is a bounded "does it converge" check, not O(history) per
+// branch.
+fn containing_branch(repo: &Repository, target: Id<'_>) -> Option<BString> {
+	let refs = repo.references().ok()?;
+	for branch in refs.local_branches().ok()?.flatten() {
+		let Ok(tip) = branch.clone().into_fully_peeled_id() else {
+			continue;
+		};
+
+		let Ok(base) = repo.merge_base(target, tip) else {
+			continue;
+		};
+		if base.detach() == target.detach() {
+			return Some(branch.name().shorten().to_owned());
+		}
+	}
+
+	None
+}
+
+// `git describe`-style info for a detached HEAD: nearest tag + distance,
+// falling back to the bare prefix when no tag is reachable
+#[derive(Debug)]
+struct Describe {
+	hash: Prefix,
+	tag: Option<(BString, usize)>,
+	branch: Option<BString>,
+}
+
+impl Describe {
+	fn new(repo: &Repository, id: Id<'_>) -> Self {
+		Describe {
+			hash: rel(id),
+			tag: nearest_tag(repo, id),
+			branch: containing_branch(repo, id),
+		}
+	}
+}
+
+impl Display for Describe {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.tag {
+			Some((tag, 0)) => write!(f, "{tag}")?,
+			Some((tag, distance)) => write!(f, "{tag}+{distance}-g{}", self.hash)?,
+			None => write!(f, "{}", self.hash)?,
+		}
+
+		if let Some(branch) = &self.branch {
+			write!(f, " ({branch})")?;
+		}
+
+		Ok(())
+	}
 }
 
 #[derive(Debug)]
 enum Head {
 	Branch(BString),
-	Commit(Prefix),
+	Commit(Describe),
 }
 
 impl Head {
@@ -37,9 +148,8 @@ impl Head {
 				Head::Branch(branch.to_owned())
 			}
 			None => {
-				let hash = head.id().unwrap();
-				let hash = rel(hash);
-				Head::Commit(hash)
+				let id = head.id().unwrap();
+				Head::Commit(Describe::new(repo, id))
 			}
 		}
 	}
@@ -49,7 +159,71 @@ impl Display for Head {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Head::Branch(branch) => write!(f, "{branch}"),
-			Head::Commit(hash) => write!(f, ":{hash}"),
+			Head::Commit(describe) => write!(f, ":{describe}"),
+		}
+	}
+}
+
+// thx https://github.com/peppe-rs/prompt
+#[derive(Debug)]
+enum Dist {
+	Ahead(usize),
+	Behind(usize),
+	Both(usize, usize),
+}
+
+impl Dist {
+	fn new(repo: &Repository, branch: &BString) -> Option<Self> {
+		let local = repo.head_id().ok()?;
+
+		// `branch_remote_tracking_ref_name` derives the `branch.<name>.*`
+		// config key itself, but needs the full `refs/heads/<name>` form,
+		// not the shortened name `Head::Branch` stores
+		let full = gix::refs::FullName::try_from(BString::from(format!("refs/heads/{branch}"))).ok()?;
+		let upstream = repo
+			.branch_remote_tracking_ref_name(full.as_ref(), Direction::Fetch)?
+			.ok()?;
+		let upstream = repo.find_reference(&upstream).ok()?.peel_to_id_in_place().ok()?;
+
+		if local == upstream {
+			return None;
+		}
+
+		let base = repo.merge_base(local, upstream).ok()?;
+
+		let ahead = repo
+			.rev_walk([local.detach()])
+			.with_hidden([base.detach()])
+			.all()
+			.ok()?
+			.count();
+		let behind = repo
+			.rev_walk([upstream.detach()])
+			.with_hidden([base.detach()])
+			.all()
+			.ok()?
+			.count();
+
+		match (ahead, behind) {
+			(0, 0) => None,
+			(ahead, 0) => Some(Dist::Ahead(ahead)),
+			(0, behind) => Some(Dist::Behind(behind)),
+			(ahead, behind) => Some(Dist::Both(ahead, behind)),
+		}
+	}
+}
+
+impl Display for Dist {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Dist::Ahead(ahead) => write!(f, "{}", format!("↑{ahead}").blue()),
+			Dist::Behind(behind) => write!(f, "{}", format!("↓{behind}").magenta()),
+			Dist::Both(ahead, behind) => write!(
+				f,
+				"{} {}",
+				format!("↑{ahead}").blue(),
+				format!("↓{behind}").magenta()
+			),
 		}
 	}
 }
@@ -66,6 +240,106 @@ impl Display for Status {
 	}
 }
 
+// working-tree / index dirty state, distinct from the rebase `Status` above
+#[derive(Debug, Default)]
+struct Dirty {
+	unstaged: bool,
+	staged: bool,
+	untracked: bool,
+	conflict: bool,
+}
+
+impl Dirty {
+	fn new(repo: &Repository) -> Self {
+		let mut dirty = Dirty::default();
+
+		let Ok(status) = repo
+			.status(gix::progress::Discard)
+			.map(|p| p.untracked_files(gix::status::UntrackedFiles::Files))
+		else {
+			return dirty;
+		};
+		let Ok(iter) = status.into_iter(None) else {
+			return dirty;
+		};
+
+		for item in iter.flatten() {
+			match item {
+				gix::status::Item::TreeIndex(_) => dirty.staged = true,
+				gix::status::Item::IndexWorktree(change) => match change {
+					gix::status::index_worktree::Item::Modification { .. } => {
+						dirty.unstaged = true;
+					}
+					gix::status::index_worktree::Item::DirectoryContents { .. } => {
+						dirty.untracked = true;
+					}
+					// rename from a tracked path onto a new, untracked one:
+					// shows as both an unstaged change on the old path and
+					// a new untracked entry, same as plain `git status`
+					gix::status::index_worktree::Item::Rewrite { .. } => {
+						dirty.unstaged = true;
+						dirty.untracked = true;
+					}
+				},
+			}
+		}
+
+		if let Ok(index) = repo.index_or_empty()
+			&& index
+				.entries()
+				.iter()
+				.any(|entry| entry.stage() != gix::index::entry::Stage::Unconflicted)
+		{
+			dirty.conflict = true;
+		}
+
+		dirty
+	}
+
+	fn is_clean(&self) -> bool {
+		!(self.unstaged || self.staged || self.untracked || self.conflict)
+	}
+}
+
+impl Display for Dirty {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.unstaged {
+			write!(f, "{}", "*".yellow())?;
+		}
+		if self.staged {
+			write!(f, "{}", "+".green())?;
+		}
+		if self.untracked {
+			write!(f, "{}", "?".cyan())?;
+		}
+		if self.conflict {
+			write!(f, "{}", "!".red())?;
+		}
+		Ok(())
+	}
+}
+
+// number of `refs/stash` reflog entries, one per `stash push`
+#[derive(Debug)]
+struct Stash(usize);
+
+impl Stash {
+	fn new(repo: &Repository) -> Option<Self> {
+		// refs/stash and its reflog are shared state, not the linked
+		// worktree's private git dir, so this has to use the common dir
+		let log = std::fs::read_to_string(repo.common_dir().join("logs/refs/stash")).ok()?;
+		let count = log.lines().filter(|line| !line.is_empty()).count();
+
+		if count == 0 { None } else { Some(Stash(count)) }
+	}
+}
+
+impl Display for Stash {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", format!("⚑{}", self.0).purple())
+	}
+}
+
 #[derive(Debug)]
 #[must_use]
 enum Mode {
@@ -100,8 +374,8 @@ impl Mode {
 				let branch = BString::from(head.trim_end());
 				Some(Head::Branch(branch))
 			} else if let Ok(head) = std::fs::read_to_string(path.join("orig-head")) {
-				let hash = hash(repo, &head);
-				Some(Head::Commit(hash))
+				let id = resolve(repo, &head);
+				Some(Head::Commit(Describe::new(repo, id)))
 			} else {
 				None
 			};
@@ -193,7 +467,25 @@ fn git() -> Result<String, Box<dyn std::error::Error>> {
 		write!(string, "{} ", mode.red())?;
 	}
 
-	write!(string, "{}{}", branch.green(), ")".green())?;
+	write!(string, "{}", branch.green())?;
+
+	if let Head::Branch(name) = &branch
+		&& let Some(dist) = Dist::new(&repo, name)
+	{
+		write!(string, " {dist}")?;
+	}
+
+	let dirty = Dirty::new(&repo);
+	if !dirty.is_clean() {
+		write!(string, " {dirty}")?;
+	}
+
+	let stash = Stash::new(&repo);
+	if let Some(stash) = stash {
+		write!(string, " {stash}")?;
+	}
+
+	write!(string, "{}", ")".green())?;
 	Ok(string)
 }
 
@@ -220,22 +512,111 @@ impl Display for Start {
 	}
 }
 
-#[repr(transparent)]
-struct Dir(PathBuf);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirMode {
+	// only the final path component
+	Short,
+	// tico-style: `~` for home, each component but the last shortened to
+	// its first character (leading dots preserved)
+	Full,
+}
+
+impl DirMode {
+	fn detect(arg: Option<&str>) -> Self {
+		match arg {
+			Some("full") => DirMode::Full,
+			_ => DirMode::Short,
+		}
+	}
+}
+
+// shortens a single path component, preserving a leading dot so
+// dotfiles collapse to `.x` instead of losing the dot entirely
+fn shorten(component: &str) -> String {
+	match component.strip_prefix('.') {
+		Some(rest) => {
+			let mut short = String::from(".");
+			if let Some(c) = rest.chars().next() {
+				short.push(c);
+			}
+			short
+		}
+		None => component.chars().next().map(String::from).unwrap_or_default(),
+	}
+}
+
+struct Dir {
+	path: PathBuf,
+	mode: DirMode,
+}
 
 impl Dir {
-	fn cwd() -> Self {
+	fn cwd(mode: DirMode) -> Self {
 		let path = std::env::current_dir().unwrap_or_default();
-		Dir(path)
+		Dir { path, mode }
+	}
+
+	// splits the path into a shortened, `~`-aware prefix and the full
+	// final component, e.g. `/home/u/src/maysh-rs` -> `("~/s/", "maysh-rs")`
+	fn tico(&self) -> (String, String) {
+		let home = std::env::var_os("HOME").map(PathBuf::from);
+		// `root` is the non-shortened anchor the path is relative to:
+		// `~` under $HOME, `/` for any other absolute path (e.g. root's
+		// own paths outside $HOME), empty otherwise
+		let (root, rel): (&str, &Path) = match &home {
+			Some(home) if self.path.starts_with(home) => ("~", self.path.strip_prefix(home).unwrap()),
+			_ if self.path.is_absolute() => ("/", self.path.as_path()),
+			_ => ("", self.path.as_path()),
+		};
+
+		let mut components: Vec<String> = rel
+			.components()
+			.filter_map(|component| match component {
+				std::path::Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+				_ => None,
+			})
+			.collect();
+
+		let Some(last) = components.pop() else {
+			return (String::new(), root.to_owned());
+		};
+
+		let joined = components
+			.iter()
+			.map(|component| shorten(component))
+			.collect::<Vec<_>>()
+			.join("/");
+
+		let prefix = match root {
+			"/" if joined.is_empty() => "/".to_owned(),
+			"/" => format!("/{joined}/"),
+			"~" if joined.is_empty() => "~/".to_owned(),
+			"~" => format!("~/{joined}/"),
+			_ if joined.is_empty() => String::new(),
+			_ => format!("{joined}/"),
+		};
+
+		(prefix, last)
 	}
 }
 
 impl Display for Dir {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		if let Some(name) = self.0.file_name() {
-			write!(f, "{}", name.display().cyan())
-		} else {
-			write!(f, "{}", self.0.display().cyan())
+		match self.mode {
+			DirMode::Short => {
+				if let Some(name) = self.path.file_name() {
+					write!(f, "{}", name.display().cyan())
+				} else {
+					write!(f, "{}", self.path.display().cyan())
+				}
+			}
+			DirMode::Full => {
+				let (prefix, last) = self.tico();
+				if !prefix.is_empty() {
+					write!(f, "{}", prefix.dimmed())?;
+				}
+				write!(f, "{}", last.cyan())
+			}
 		}
 	}
 }
@@ -256,14 +637,113 @@ impl Display for User {
 	}
 }
 
+// the non-printing markers bash/zsh need around color escapes, so the
+// shell doesn't count them toward the visible prompt width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShellType {
+	Bash,
+	Zsh,
+	Unknown,
+}
+
+impl ShellType {
+	fn detect(arg: Option<&str>) -> Self {
+		let shell = arg
+			.map(str::to_owned)
+			.or_else(|| std::env::var("SHELL").ok());
+
+		match shell
+			.as_deref()
+			.map(Path::new)
+			.and_then(Path::file_name)
+			.and_then(|name| name.to_str())
+		{
+			Some("bash") => ShellType::Bash,
+			Some("zsh") => ShellType::Zsh,
+			_ => ShellType::Unknown,
+		}
+	}
+
+	fn markers(self) -> (&'static str, &'static str) {
+		match self {
+			ShellType::Bash => ("\\[", "\\]"),
+			ShellType::Zsh => ("%{", "%}"),
+			ShellType::Unknown => ("", ""),
+		}
+	}
+}
+
+// wraps every SGR escape sequence (`\x1b[...m`) written to it in the
+// shell's non-printing markers, so raw ANSI bytes from owo_colors don't
+// get counted toward the prompt width
+struct ShellWriter {
+	shell: ShellType,
+	buf: String,
+}
+
+impl ShellWriter {
+	fn new(shell: ShellType) -> Self {
+		ShellWriter {
+			shell,
+			buf: String::new(),
+		}
+	}
+
+	fn finish(self) -> String {
+		self.buf
+	}
+}
+
+impl Write for ShellWriter {
+	fn write_str(&mut self, s: &str) -> std::fmt::Result {
+		if self.shell == ShellType::Unknown {
+			self.buf.push_str(s);
+			return Ok(());
+		}
+
+		let (open, close) = self.shell.markers();
+		let mut rest = s;
+
+		while let Some(start) = rest.find('\x1b') {
+			self.buf.push_str(&rest[..start]);
+			let tail = &rest[start..];
+
+			match tail.find('m') {
+				Some(end) => {
+					self.buf.push_str(open);
+					self.buf.push_str(&tail[..=end]);
+					self.buf.push_str(close);
+					rest = &tail[end + 1..];
+				}
+				None => {
+					self.buf.push_str(tail);
+					rest = "";
+				}
+			}
+		}
+
+		self.buf.push_str(rest);
+		Ok(())
+	}
+}
+
 fn main() {
 	let usr = User::current();
 	let start = Start::new(&usr);
-	let dir = Dir::cwd();
 
-	if let Ok(git) = git() {
-		print!("{start} {usr} {dir} {git} >> ");
+	let dir_mode = DirMode::detect(std::env::args().nth(2).as_deref());
+	let dir = Dir::cwd(dir_mode);
+
+	let shell = ShellType::detect(std::env::args().nth(1).as_deref());
+	let mut writer = ShellWriter::new(shell);
+
+	let result = if let Ok(git) = git() {
+		write!(writer, "{start} {usr} {dir} {git} >> ")
 	} else {
-		print!("{start} {usr} {dir} >> ");
+		write!(writer, "{start} {usr} {dir} >> ")
+	};
+
+	if result.is_ok() {
+		print!("{}", writer.finish());
 	}
 }